@@ -4,6 +4,7 @@ use std::{
 };
 
 use clap::Parser;
+use unicode_width::UnicodeWidthChar;
 
 type MyResult<T> = Result<T, Box<dyn std::error::Error>>;
 
@@ -52,6 +53,21 @@ pub struct Config {
         value_name = "CHARS"
     )]
     chars: bool,
+
+    #[arg(
+        short = 'L',
+        long = "max-line-length",
+        help = "The display width of the widest line in each input file is written to the standard output.",
+        value_name = "MAX_LINE_LENGTH"
+    )]
+    max_line_length: bool,
+
+    #[arg(
+        long = "files0-from",
+        help = "Read input from the files specified by NUL-terminated names in file F; if F is -, read names from standard input",
+        value_name = "F"
+    )]
+    files0_from: Option<String>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -60,39 +76,137 @@ struct FileInfo {
     num_words: usize,
     num_bytes: usize,
     num_chars: usize,
+    num_max_line: usize,
+}
+
+#[derive(Debug)]
+enum WcError {
+    CannotOpen(String, std::io::Error),
+    ReadFailed(String, Box<dyn std::error::Error>),
+    IsDirectory(String),
+    EmptyFileName,
+    SomeInputsFailed,
 }
 
+impl std::fmt::Display for WcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WcError::CannotOpen(path, e) => write!(f, "wc: cannot open '{}' for reading: {}", path, e),
+            WcError::ReadFailed(path, e) => write!(f, "wc: '{}': {}", path, e),
+            WcError::IsDirectory(path) => write!(f, "wc: '{}': Is a directory", path),
+            WcError::EmptyFileName => write!(f, "wc: invalid zero-length file name"),
+            WcError::SomeInputsFailed => write!(f, "wc: some inputs could not be processed"),
+        }
+    }
+}
+
+impl std::error::Error for WcError {}
+
 pub fn get_args() -> MyResult<Config> {
     let config = Config::parse();
     Ok(config)
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
+fn open(filename: &str) -> Result<Box<dyn BufRead>, WcError> {
     match filename {
         "-" => Ok(Box::new(BufReader::new(std::io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+        _ => {
+            let metadata = std::fs::metadata(filename)
+                .map_err(|e| WcError::CannotOpen(filename.to_string(), e))?;
+            if metadata.is_dir() {
+                return Err(WcError::IsDirectory(filename.to_string()));
+            }
+            let file =
+                File::open(filename).map_err(|e| WcError::CannotOpen(filename.to_string(), e))?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+    }
+}
+
+fn stat_file_size(filename: &str) -> Option<u64> {
+    if filename == "-" {
+        return None;
     }
+    let metadata = std::fs::metadata(filename).ok()?;
+    metadata.is_file().then_some(metadata.len())
 }
 
-fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
+// Invalid UTF-8 bytes count as one character each, matching GNU wc.
+fn tally_line(line: &[u8], num_lines: &mut usize, num_words: &mut usize, num_chars: &mut usize, num_max_line: &mut usize) {
+    *num_lines += 1;
+
+    let mut in_word = false;
+    for chunk in line.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            *num_chars += 1;
+            if c.is_whitespace() {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                *num_words += 1;
+            }
+        }
+        for _ in chunk.invalid() {
+            *num_chars += 1;
+            if !in_word {
+                in_word = true;
+                *num_words += 1;
+            }
+        }
+    }
+
+    let trimmed = line.strip_suffix(b"\n").unwrap_or(line);
+    let trimmed = trimmed.strip_suffix(b"\r").unwrap_or(trimmed);
+
+    let mut col = 0;
+    for chunk in trimmed.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if c == '\t' {
+                col += 8 - (col % 8);
+            } else {
+                col += UnicodeWidthChar::width(c).unwrap_or(0);
+            }
+        }
+        col += chunk.invalid().len();
+    }
+    *num_max_line = (*num_max_line).max(col);
+}
+
+fn count(mut reader: impl BufRead) -> MyResult<FileInfo> {
     let mut num_lines = 0;
     let mut num_words = 0;
     let mut num_bytes = 0;
     let mut num_chars = 0;
+    let mut num_max_line = 0;
 
-    let mut line = String::new();
+    let mut line: Vec<u8> = Vec::new();
 
     loop {
-        let line_bytes = file.read_line(&mut line)?;
-        if line_bytes == 0 {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
             break;
         }
 
-        num_lines += 1;
-        num_bytes += line_bytes;
-        num_words += line.split_whitespace().count();
-        num_chars += line.chars().count();
-        line.clear();
+        match memchr::memchr(b'\n', available) {
+            Some(pos) => {
+                line.extend_from_slice(&available[..=pos]);
+                let consumed = pos + 1;
+                reader.consume(consumed);
+                num_bytes += consumed;
+                tally_line(&line, &mut num_lines, &mut num_words, &mut num_chars, &mut num_max_line);
+                line.clear();
+            }
+            None => {
+                let consumed = available.len();
+                line.extend_from_slice(available);
+                reader.consume(consumed);
+                num_bytes += consumed;
+            }
+        }
+    }
+
+    if !line.is_empty() {
+        tally_line(&line, &mut num_lines, &mut num_words, &mut num_chars, &mut num_max_line);
     }
 
     Ok(FileInfo {
@@ -100,6 +214,7 @@ fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
         num_lines,
         num_chars,
         num_words,
+        num_max_line,
     })
 }
 
@@ -111,10 +226,111 @@ fn format_field(value: usize, show: bool) -> String {
     }
 }
 
+fn process_one(filename: &str, config: &Config, bytes_only: bool) -> Result<FileInfo, WcError> {
+    if bytes_only {
+        if let Some(len) = stat_file_size(filename) {
+            println!("{} {}", format_field(len as usize, config.bytes), filename);
+            return Ok(FileInfo {
+                num_lines: 0,
+                num_words: 0,
+                num_bytes: len as usize,
+                num_chars: 0,
+                num_max_line: 0,
+            });
+        }
+    }
+
+    let file = open(filename)?;
+    let res = count(file).map_err(|e| WcError::ReadFailed(filename.to_string(), e))?;
+    println!(
+        "{}{}{}{}{}{}",
+        format_field(res.num_lines, config.lines),
+        format_field(res.num_words, config.words),
+        format_field(res.num_bytes, config.bytes),
+        format_field(res.num_chars, config.chars),
+        format_field(res.num_max_line, config.max_line_length),
+        if filename == "-" {
+            "".to_string()
+        } else {
+            format!(" {}", filename)
+        }
+    );
+    Ok(res)
+}
+
+// Returns whether any pattern failed to expand (invalid glob, or no matches).
+fn expand_globs(files: &[String]) -> (Vec<String>, bool) {
+    let mut expanded = Vec::new();
+    let mut had_error = false;
+    for file in files {
+        if file == "-" || !file.contains(['*', '?', '[']) {
+            expanded.push(file.clone());
+            continue;
+        }
+
+        match glob::glob(file) {
+            Ok(paths) => {
+                let mut matches: Vec<String> = paths
+                    .filter_map(|entry| entry.ok())
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                if matches.is_empty() {
+                    eprintln!("wc: {}: no matches found", file);
+                    had_error = true;
+                } else {
+                    matches.sort();
+                    expanded.append(&mut matches);
+                }
+            }
+            Err(e) => {
+                eprintln!("wc: {}: {}", file, e);
+                had_error = true;
+            }
+        }
+    }
+    (expanded, had_error)
+}
+
+// Returns whether any entry was rejected (empty name, or "-" reused from a stdin-sourced list).
+fn for_each_files0_entry(source: &str, mut on_name: impl FnMut(&str)) -> MyResult<bool> {
+    let mut reader = open(source)?;
+    let mut buf = Vec::new();
+    let mut had_error = false;
+    loop {
+        buf.clear();
+        let n = reader.read_until(0, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        if buf.last() == Some(&0) {
+            buf.pop();
+        }
+        let name = String::from_utf8_lossy(&buf);
+        if name.is_empty() {
+            eprintln!("{}", WcError::EmptyFileName);
+            had_error = true;
+            continue;
+        }
+        if source == "-" && name == "-" {
+            eprintln!("wc: when reading file names from stdin, no file name of '-' allowed");
+            had_error = true;
+            continue;
+        }
+        on_name(&name);
+    }
+    Ok(had_error)
+}
+
 pub fn run(mut config: Config) -> MyResult<()> {
-    if [config.lines, config.words, config.bytes, config.chars]
-        .iter()
-        .all(|v| v == &false)
+    if [
+        config.lines,
+        config.words,
+        config.bytes,
+        config.chars,
+        config.max_line_length,
+    ]
+    .iter()
+    .all(|v| v == &false)
     {
         config.lines = true;
         config.words = true;
@@ -125,42 +341,65 @@ pub fn run(mut config: Config) -> MyResult<()> {
     let mut total_words = 0;
     let mut total_bytes = 0;
     let mut total_chars = 0;
+    let mut total_max_line = 0;
+    let mut total_files = 0;
+
+    let bytes_only = config.bytes
+        && !config.lines
+        && !config.words
+        && !config.chars
+        && !config.max_line_length;
+
+    let mut fold_in = |res: FileInfo| {
+        total_lines += res.num_lines;
+        total_words += res.num_words;
+        total_bytes += res.num_bytes;
+        total_chars += res.num_chars;
+        total_max_line = total_max_line.max(res.num_max_line);
+    };
 
-    for filename in &config.files {
-        match open(filename) {
-            Err(e) => eprintln!("{}: {}", filename, e),
-            Ok(file) => {
-                if let Ok(res) = count(file) {
-                    println!(
-                        "{}{}{}{}{}",
-                        format_field(res.num_lines, config.lines),
-                        format_field(res.num_words, config.words),
-                        format_field(res.num_bytes, config.bytes),
-                        format_field(res.num_chars, config.chars),
-                        if filename == "-" {
-                            "".to_string()
-                        } else {
-                            format!(" {}", filename)
-                        }
-                    );
-
-                    total_lines += res.num_lines;
-                    total_words += res.num_words;
-                    total_bytes += res.num_bytes;
-                    total_chars += res.num_chars;
+    let mut had_error = false;
+
+    if let Some(files0_from) = config.files0_from.clone() {
+        had_error |= for_each_files0_entry(&files0_from, |name| {
+            total_files += 1;
+            match process_one(name, &config, bytes_only) {
+                Ok(res) => fold_in(res),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    had_error = true;
+                }
+            }
+        })?;
+    } else {
+        let (filenames, glob_had_error) = expand_globs(&config.files);
+        had_error |= glob_had_error;
+        for filename in &filenames {
+            total_files += 1;
+            match process_one(filename, &config, bytes_only) {
+                Ok(res) => fold_in(res),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    had_error = true;
                 }
             }
         }
     }
-    if config.files.len() > 1 {
+
+    if total_files > 1 {
         println!(
-            "{}{}{}{} total",
+            "{}{}{}{}{} total",
             format_field(total_lines, config.lines),
             format_field(total_words, config.words),
             format_field(total_bytes, config.bytes),
-            format_field(total_chars, config.chars)
+            format_field(total_chars, config.chars),
+            format_field(total_max_line, config.max_line_length)
         );
     }
+
+    if had_error {
+        return Err(Box::new(WcError::SomeInputsFailed));
+    }
     Ok(())
 }
 
@@ -180,8 +419,122 @@ mod tests {
             num_words: 10,
             num_chars: 48,
             num_bytes: 48,
+            num_max_line: 46,
         };
 
         assert_eq!(info.unwrap(), expected);
     }
+
+    #[test]
+    fn test_count_max_line_length() {
+        let text = "tiny\nwide column with tabs\tand text\n";
+        let info = count(Cursor::new(text));
+        assert!(info.is_ok());
+        assert_eq!(info.unwrap().num_max_line, 32);
+    }
+
+    #[test]
+    fn test_stat_file_size() {
+        let path = std::env::temp_dir().join("wcr_stat_file_size_test.txt");
+        std::fs::write(&path, "hello\n").unwrap();
+        let size = stat_file_size(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(size, Some(6));
+    }
+
+    #[test]
+    fn test_stat_file_size_stdin() {
+        assert_eq!(stat_file_size("-"), None);
+    }
+
+    #[test]
+    fn test_count_invalid_utf8() {
+        let bytes = b"ok \xffbad\n";
+        let info = count(Cursor::new(&bytes[..])).unwrap();
+        assert_eq!(info.num_bytes, bytes.len());
+        assert_eq!(info.num_lines, 1);
+        assert_eq!(info.num_words, 2);
+        assert_eq!(info.num_chars, 8);
+    }
+
+    #[test]
+    fn test_for_each_files0_entry() {
+        let path = std::env::temp_dir().join("wcr_files0_from_test.txt");
+        std::fs::write(&path, b"one\0two\0\0three\0".as_slice()).unwrap();
+
+        let mut names = Vec::new();
+        for_each_files0_entry(path.to_str().unwrap(), |name| names.push(name.to_string())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(names, vec!["one", "two", "three"]);
+    }
+
+    #[test]
+    fn test_expand_globs() {
+        let dir = std::env::temp_dir().join("wcr_expand_globs_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.txt"), "").unwrap();
+        std::fs::write(dir.join("a.txt"), "").unwrap();
+
+        let pattern = dir.join("*.txt").to_string_lossy().into_owned();
+        let files = vec!["-".to_string(), pattern];
+        let (expanded, had_error) = expand_globs(&files);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!had_error);
+        assert_eq!(
+            expanded,
+            vec![
+                "-".to_string(),
+                dir.join("a.txt").to_string_lossy().into_owned(),
+                dir.join("b.txt").to_string_lossy().into_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_one_missing_file() {
+        let err = process_one("wcr_no_such_file_xyz.txt", &Config::parse_from(["wc"]), false)
+            .unwrap_err();
+        assert!(matches!(err, WcError::CannotOpen(_, _)));
+        assert!(err.to_string().contains("wcr_no_such_file_xyz.txt"));
+    }
+
+    #[test]
+    fn test_process_one_directory() {
+        let dir = std::env::temp_dir().join("wcr_process_one_dir_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let err = process_one(dir.to_str().unwrap(), &Config::parse_from(["wc"]), false)
+            .unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(err, WcError::IsDirectory(_)));
+    }
+
+    #[test]
+    fn test_expand_globs_no_matches() {
+        let pattern = std::env::temp_dir()
+            .join("wcr_no_such_glob_dir_xyz/*.nope")
+            .to_string_lossy()
+            .into_owned();
+        let (expanded, had_error) = expand_globs(&[pattern]);
+        assert!(expanded.is_empty());
+        assert!(had_error);
+    }
+
+    #[test]
+    fn test_run_fails_on_missing_file() {
+        let config = Config::parse_from(["wc", "wcr_no_such_file_xyz.txt"]);
+        assert!(matches!(run(config), Err(e) if matches!(e.downcast_ref::<WcError>(), Some(WcError::SomeInputsFailed))));
+    }
+
+    #[test]
+    fn test_run_fails_on_glob_no_matches() {
+        let pattern = std::env::temp_dir()
+            .join("wcr_no_such_glob_dir_xyz/*.nope")
+            .to_string_lossy()
+            .into_owned();
+        let config = Config::parse_from(["wc", &pattern]);
+        assert!(matches!(run(config), Err(e) if matches!(e.downcast_ref::<WcError>(), Some(WcError::SomeInputsFailed))));
+    }
 }